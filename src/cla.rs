@@ -0,0 +1,32 @@
+use crate::core::PeerAddress;
+use std::fmt::Debug;
+
+/// CLA agent names compiled into this build. `get_first_cla()` picks the
+/// first entry of the peer's own `cla_list` found here; the order of this
+/// list does not affect that choice.
+const SUPPORTED_AGENTS: &[&str] = &["mtcp", "tcp", "http"];
+
+pub fn convergency_layer_agents() -> &'static [&'static str] {
+    SUPPORTED_AGENTS
+}
+
+/// A convergence layer agent, e.g. the mtcp or tcp implementation.
+pub trait ConvergencyLayerAgent: Debug {
+    fn name(&self) -> &str;
+
+    /// Attempts to reach `remote` over this CLA, returning whether it
+    /// succeeded. Used by `core::process_peer_conn_states()` to drive a
+    /// peer's connection state machine and RTT history off a real
+    /// transmission attempt rather than bookkeeping alone.
+    fn probe(&self, remote: &ClaSender) -> bool;
+}
+
+/// A destination to hand a bundle to a specific CLA for transmission.
+/// `remote` is a `PeerAddress`, not a bare `IpAddr`, so non-IP CLAs (BLE,
+/// LoRa, serial, ...) can be addressed too; see `PeerAddress`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaSender {
+    pub remote: PeerAddress,
+    pub port: Option<u16>,
+    pub agent: String,
+}
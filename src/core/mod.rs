@@ -1,7 +1,10 @@
 pub mod application_agent;
 pub mod bundlepack;
 pub mod helpers;
+pub mod peer_exchange;
+pub mod peer_store;
 pub mod processing;
+pub mod protocol_handler;
 pub mod store;
 
 use crate::cla::ConvergencyLayerAgent;
@@ -13,9 +16,25 @@ use crate::STORE;
 use application_agent::ApplicationAgent;
 use bp7::EndpointID;
 use log::{debug, error, info};
+use protocol_handler::{ProtocolHandler, ProtocolHandlerRegistry};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::net::IpAddr;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Base interval between reconnection attempts; the actual delay grows
+/// exponentially with the number of consecutive failures.
+pub const CONN_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+/// Upper bound on the exponent applied to `CONN_RETRY_INTERVAL`, so the
+/// backoff doesn't grow unbounded for long-dead peers.
+pub const CONN_MAX_RETRY_EXPONENT: u32 = 6;
+/// Number of consecutive failed transmissions after which a peer is
+/// considered `Abandoned`.
+pub const CONN_MAX_RETRIES: usize = 10;
+/// Number of recent RTT samples kept per peer for the moving average.
+pub const RTT_HISTORY_LEN: usize = 10;
+/// A peer is considered degraded once its average RTT exceeds this.
+pub const DEGRADED_RTT_THRESHOLD: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum PeerType {
@@ -23,31 +42,158 @@ pub enum PeerType {
     Dynamic,
 }
 
+/// A peer's address, interpreted by whichever CLA is sending to it.
+///
+/// `Ip` covers classic socket-based CLAs (TCP/UDP-CL). `Generic` and
+/// `BroadcastGeneric` carry an opaque, CLA-specific address or URI for
+/// links that have no IP identity at all (BLE, LoRa, serial, ...); the CLA
+/// implementation is responsible for parsing its own scheme out of the
+/// string.
+///
+/// Serializes through a single tagged string (see `from_str`/`Display`),
+/// not `#[serde(untagged)]`: `Generic` and `BroadcastGeneric` both wrap a
+/// bare `String`, so an untagged enum can't tell them apart on the way
+/// back in and would silently collapse every `BroadcastGeneric` into a
+/// `Generic`. A bare IP string (no tag) still deserializes as
+/// `PeerAddress::Ip`, which keeps this backward compatible with peers
+/// persisted or received on the wire before this type existed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum PeerAddress {
+    Ip(IpAddr),
+    Generic(String),
+    BroadcastGeneric(String),
+}
+
+/// Tag prefixing the wire/`Display` form of a `Generic` address.
+const GENERIC_TAG: &str = "generic:";
+/// Tag prefixing the wire/`Display` form of a `BroadcastGeneric` address.
+const BROADCAST_GENERIC_TAG: &str = "broadcast:";
+
+impl std::fmt::Display for PeerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerAddress::Ip(addr) => write!(f, "{}", addr),
+            PeerAddress::Generic(addr) => write!(f, "{}{}", GENERIC_TAG, addr),
+            PeerAddress::BroadcastGeneric(addr) => write!(f, "{}{}", BROADCAST_GENERIC_TAG, addr),
+        }
+    }
+}
+
+impl From<IpAddr> for PeerAddress {
+    fn from(addr: IpAddr) -> Self {
+        PeerAddress::Ip(addr)
+    }
+}
+
+impl From<PeerAddress> for String {
+    fn from(addr: PeerAddress) -> String {
+        addr.to_string()
+    }
+}
+
+impl TryFrom<String> for PeerAddress {
+    type Error = std::convert::Infallible;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::str::FromStr for PeerAddress {
+    type Err = std::convert::Infallible;
+
+    /// Parses the tagged `Display` form back into a `PeerAddress`.
+    /// Untagged input is tried as an `IpAddr` first, for backward
+    /// compatibility with peers persisted or received before this type
+    /// existed; anything else untagged becomes a `Generic` address.
+    /// Never fails.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::PeerAddress;
+    ///
+    /// let broadcast: PeerAddress = "broadcast:ble-mesh-1".parse().unwrap();
+    /// assert_eq!(broadcast, PeerAddress::BroadcastGeneric("ble-mesh-1".into()));
+    /// assert_ne!(broadcast, PeerAddress::Generic("ble-mesh-1".into()));
+    ///
+    /// let round_tripped: PeerAddress = broadcast.to_string().parse().unwrap();
+    /// assert_eq!(round_tripped, broadcast);
+    ///
+    /// let legacy: PeerAddress = "127.0.0.1".parse().unwrap();
+    /// assert_eq!(legacy, PeerAddress::Ip("127.0.0.1".parse().unwrap()));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix(BROADCAST_GENERIC_TAG) {
+            return Ok(PeerAddress::BroadcastGeneric(addr.to_string()));
+        }
+        if let Some(addr) = s.strip_prefix(GENERIC_TAG) {
+            return Ok(PeerAddress::Generic(addr.to_string()));
+        }
+        Ok(match s.parse::<IpAddr>() {
+            Ok(addr) => PeerAddress::Ip(addr),
+            Err(_) => PeerAddress::Generic(s.to_string()),
+        })
+    }
+}
+
+/// Tracks whether we believe we can currently reach a peer over its CLAs.
+///
+/// Not `Serialize`/`Deserialize`: `next_try` is an `Instant`, which has no
+/// portable wire representation. `DtnPeer::conn_state` is `#[serde(skip)]`
+/// for the same reason.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerConnState {
+    /// Last transmission attempt succeeded.
+    Connected,
+    /// At least one transmission has failed; `retries` consecutive
+    /// failures have been observed and the next attempt isn't due until
+    /// `next_try`.
+    Waiting { retries: usize, next_try: Instant },
+    /// `CONN_MAX_RETRIES` consecutive failures were observed for a
+    /// dynamic peer; it is no longer retried and will be dropped by
+    /// `process_peers()`.
+    Abandoned,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DtnPeer {
     pub eid: EndpointID,
-    pub addr: IpAddr,
+    pub addr: PeerAddress,
     pub con_type: PeerType,
     pub cla_list: Vec<(String, Option<u16>)>,
     pub last_contact: u64,
+    #[serde(skip, default = "default_conn_state")]
+    pub conn_state: PeerConnState,
+    /// Recent round-trip samples per CLA agent name, most recent last,
+    /// each capped at `RTT_HISTORY_LEN` entries.
+    #[serde(skip, default)]
+    pub rtt_history: HashMap<String, VecDeque<Duration>>,
+}
+
+fn default_conn_state() -> PeerConnState {
+    PeerConnState::Connected
 }
 
 impl DtnPeer {
     pub fn new(
         eid: EndpointID,
-        addr: IpAddr,
+        addr: impl Into<PeerAddress>,
         con_type: PeerType,
         cla_list: Vec<(String, Option<u16>)>,
     ) -> DtnPeer {
         DtnPeer {
             eid,
-            addr,
+            addr: addr.into(),
             con_type,
             cla_list,
             last_contact: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            conn_state: PeerConnState::Connected,
+            rtt_history: HashMap::new(),
         }
     }
     /// Example
@@ -95,11 +241,130 @@ impl DtnPeer {
     pub fn get_node_name(&self) -> String {
         self.eid.node_part().unwrap_or_default()
     }
+
+    /// Whether the peer has been marked `Abandoned`, i.e. is no longer
+    /// considered reachable.
+    pub fn is_abandoned(&self) -> bool {
+        self.conn_state == PeerConnState::Abandoned
+    }
+
+    /// Record a failed transmission attempt through one of this peer's
+    /// CLAs, advancing the exponential-backoff state machine.
+    ///
+    /// Static peers never become `Abandoned`; they keep retrying at the
+    /// maximum backoff interval indefinitely.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use dtn7::core::*;
+    ///
+    /// let mut peer = helpers::rnd_peer();
+    /// peer.con_type = PeerType::Dynamic;
+    /// for _ in 0..CONN_MAX_RETRIES {
+    ///     assert_ne!(peer.conn_state, PeerConnState::Abandoned);
+    ///     peer.report_failed_transmission();
+    /// }
+    /// assert!(peer.is_abandoned());
+    ///
+    /// peer.report_successful_transmission();
+    /// assert_eq!(peer.conn_state, PeerConnState::Connected);
+    /// ```
+    pub fn report_failed_transmission(&mut self) {
+        let retries = match self.conn_state {
+            PeerConnState::Waiting { retries, .. } => retries + 1,
+            _ => 1,
+        };
+        if retries >= CONN_MAX_RETRIES && self.con_type == PeerType::Dynamic {
+            self.conn_state = PeerConnState::Abandoned;
+            return;
+        }
+        let exponent = retries.min(CONN_MAX_RETRY_EXPONENT as usize) as u32;
+        let next_try = Instant::now() + CONN_RETRY_INTERVAL * 2u32.pow(exponent);
+        self.conn_state = PeerConnState::Waiting { retries, next_try };
+    }
+
+    /// Record a successful transmission, resetting the backoff state.
+    pub fn report_successful_transmission(&mut self) {
+        self.conn_state = PeerConnState::Connected;
+    }
+
+    /// Record a newly observed round-trip sample for transmissions sent
+    /// over `cla`, evicting the oldest sample once `RTT_HISTORY_LEN` is
+    /// exceeded.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dtn7::core::*;
+    ///
+    /// let mut peer = helpers::rnd_peer();
+    /// assert_eq!(peer.avg_rtt(), None);
+    ///
+    /// peer.add_rtt_sample("mtcp", Duration::from_millis(100));
+    /// peer.add_rtt_sample("mtcp", Duration::from_millis(300));
+    /// assert_eq!(peer.avg_rtt(), Some(Duration::from_millis(200)));
+    /// assert!(!peer.is_degraded());
+    ///
+    /// peer.add_rtt_sample("mtcp", Duration::from_secs(6));
+    /// assert!(peer.is_degraded());
+    /// ```
+    pub fn add_rtt_sample(&mut self, cla: &str, rtt: Duration) {
+        let history = self
+            .rtt_history
+            .entry(cla.to_string())
+            .or_insert_with(VecDeque::new);
+        if history.len() >= RTT_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(rtt);
+    }
+
+    /// Average RTT observed for a given CLA, or `None` if no samples have
+    /// been recorded yet.
+    pub fn avg_rtt_for(&self, cla: &str) -> Option<Duration> {
+        let history = self.rtt_history.get(cla)?;
+        if history.is_empty() {
+            return None;
+        }
+        let sum: Duration = history.iter().sum();
+        Some(sum / history.len() as u32)
+    }
+
+    /// Average RTT across all CLAs this peer has been reached through, or
+    /// `None` if no samples have been recorded for any of them yet.
+    pub fn avg_rtt(&self) -> Option<Duration> {
+        let samples: Vec<Duration> = self.rtt_history.values().flatten().copied().collect();
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<Duration>() / samples.len() as u32)
+    }
+
+    /// Whether the peer's observed average RTT exceeds
+    /// `DEGRADED_RTT_THRESHOLD`. Peers with no samples yet are not
+    /// considered degraded.
+    pub fn is_degraded(&self) -> bool {
+        self.avg_rtt()
+            .map(|rtt| rtt > DEGRADED_RTT_THRESHOLD)
+            .unwrap_or(false)
+    }
+
+    /// Seconds since the Unix epoch at which this peer was last seen.
+    pub fn last_seen(&self) -> u64 {
+        self.last_contact
+    }
+
+    /// Returns a sender for the first CLA entry this node also supports.
+    /// `ClaSender::remote` carries this peer's `PeerAddress` unchanged;
+    /// each CLA is responsible for interpreting it (socket address, opaque
+    /// URI, ...).
     pub fn get_first_cla(&self) -> Option<crate::cla::ClaSender> {
         for c in self.cla_list.iter() {
             if crate::cla::convergency_layer_agents().contains(&c.0.as_str()) {
                 let sender = crate::cla::ClaSender {
-                    remote: self.addr,
+                    remote: self.addr.clone(),
                     port: c.1,
                     agent: c.0.clone(),
                 };
@@ -108,6 +373,64 @@ impl DtnPeer {
         }
         None
     }
+
+    /// Like `get_first_cla()`, but when the peer advertises more than one
+    /// usable CLA, prefers the one with the lowest observed average RTT
+    /// instead of the first match. CLAs without any samples yet are
+    /// treated as worse than any measured one, and ties fall back to
+    /// `cla_list` order.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dtn7::core::*;
+    ///
+    /// let mut peer = helpers::rnd_peer();
+    /// peer.cla_list = vec![("mtcp".into(), None), ("tcp".into(), Some(4556))];
+    /// peer.add_rtt_sample("mtcp", Duration::from_millis(800));
+    /// peer.add_rtt_sample("tcp", Duration::from_millis(50));
+    ///
+    /// assert_eq!(peer.get_best_cla().unwrap().agent, "tcp");
+    /// assert_eq!(peer.get_first_cla().unwrap().agent, "mtcp");
+    /// ```
+    pub fn get_best_cla(&self) -> Option<crate::cla::ClaSender> {
+        self.cla_list
+            .iter()
+            .filter(|c| crate::cla::convergency_layer_agents().contains(&c.0.as_str()))
+            .min_by_key(|c| self.avg_rtt_for(&c.0).unwrap_or(Duration::MAX))
+            .map(|c| crate::cla::ClaSender {
+                remote: self.addr.clone(),
+                port: c.1,
+                agent: c.0.clone(),
+            })
+    }
+}
+
+/// Lightweight, `DtnStatistics`-style snapshot of a peer's reachability
+/// for reporting to routing agents or the management API.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PeerReport {
+    pub eid: EndpointID,
+    pub con_type: PeerType,
+    pub avg_rtt_ms: Option<u128>,
+    pub last_seen: u64,
+}
+
+/// Snapshot of all currently known peers' reachability, for routing
+/// agents that want to prefer faster contacts.
+pub fn peers_report() -> Vec<PeerReport> {
+    PEERS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|p| PeerReport {
+            eid: p.eid.clone(),
+            con_type: p.con_type.clone(),
+            avg_rtt_ms: p.avg_rtt().map(|d| d.as_millis()),
+            last_seen: p.last_seen(),
+        })
+        .collect()
 }
 pub fn peers_get_for_node(eid: &EndpointID) -> Option<DtnPeer> {
     for (_, p) in PEERS.lock().unwrap().iter() {
@@ -149,6 +472,7 @@ pub struct DtnCore {
     pub endpoints: Vec<Box<dyn ApplicationAgent + Send>>,
     pub cl_list: Vec<Box<dyn ConvergencyLayerAgent>>,
     pub routing_agent: Box<RoutingAgent>,
+    pub protocol_handlers: ProtocolHandlerRegistry,
 }
 
 impl Default for DtnCore {
@@ -164,6 +488,7 @@ impl DtnCore {
             cl_list: Vec::new(),
             //routing_agent: Box::new(crate::routing::flooding::FloodingRoutingAgent::new()),
             routing_agent: Box::new(crate::routing::epidemic::EpidemicRoutingAgent::new()),
+            protocol_handlers: ProtocolHandlerRegistry::new(),
         }
     }
 
@@ -178,6 +503,61 @@ impl DtnCore {
             .position(|n| n.eid() == aa.eid())
             .map(|e| self.endpoints.remove(e));
     }
+
+    /// Registers a handler for a reserved administrative-record /
+    /// message-type code range, so downstream control protocols (peer
+    /// exchange, custom admin records, experimental routing metadata) can
+    /// be plugged in without editing core bundle processing.
+    pub fn register_protocol_handler<T: 'static + ProtocolHandler + Send>(&mut self, handler: T) {
+        info!(
+            "Registered new protocol handler for code range: {:?}",
+            handler.code_range()
+        );
+        self.protocol_handlers.register(handler);
+    }
+
+    /// Routes a typed administrative/control payload to whichever
+    /// registered protocol handler claims `code`, if any. Called from
+    /// `core::processing::dispatch_administrative_record()` when an
+    /// incoming bundle's administrative record or payload code falls in
+    /// `protocol_handler::CUSTOM_PROTOCOL_RANGE`.
+    ///
+    /// Example
+    ///
+    /// ```
+    /// use bp7::EndpointID;
+    /// use dtn7::core::protocol_handler::ProtocolHandler;
+    /// use dtn7::core::DtnCore;
+    ///
+    /// #[derive(Debug)]
+    /// struct EchoHandler;
+    /// impl ProtocolHandler for EchoHandler {
+    ///     fn code_range(&self) -> (u8, u8) {
+    ///         (200, 200)
+    ///     }
+    ///     fn handle(&mut self, _code: u8, payload: &[u8], _sender: &EndpointID) -> Option<Vec<u8>> {
+    ///         Some(payload.to_vec())
+    ///     }
+    /// }
+    ///
+    /// let mut core = DtnCore::new();
+    /// core.register_protocol_handler(EchoHandler);
+    ///
+    /// let sender = EndpointID::new("dtn://node1/").unwrap();
+    /// assert_eq!(
+    ///     core.dispatch_protocol_message(200, b"ping", &sender),
+    ///     Some(b"ping".to_vec())
+    /// );
+    /// assert_eq!(core.dispatch_protocol_message(1, b"ping", &sender), None);
+    /// ```
+    pub fn dispatch_protocol_message(
+        &mut self,
+        code: u8,
+        payload: &[u8],
+        sender: &EndpointID,
+    ) -> Option<Vec<u8>> {
+        self.protocol_handlers.dispatch(code, payload, sender)
+    }
     pub fn eids(&self) -> Vec<String> {
         self.endpoints.iter().map(|e| e.eid().to_string()).collect()
     }
@@ -213,10 +593,64 @@ impl DtnCore {
     }
 }
 
-/// Removes peers from global peer list that haven't been seen in a while.
+/// Removes peers from global peer list that haven't been seen in a while
+/// or that have been marked `Abandoned` after repeated connection failures.
 pub fn process_peers() {
-    PEERS
+    let stale: Vec<String> = PEERS
         .lock()
         .unwrap()
-        .retain(|_k, v| v.con_type == PeerType::Static || v.still_valid());
+        .iter()
+        .filter(|(_, v)| !(v.con_type == PeerType::Static || (v.still_valid() && !v.is_abandoned())))
+        .map(|(k, _)| k.clone())
+        .collect();
+    for node_name in stale {
+        peer_store::remove_peer(&node_name);
+    }
+}
+
+/// Scans `PEERS` for peers currently in `Waiting` whose `next_try` has
+/// elapsed and actually attempts one reconnection each, via whichever of
+/// `cl_list`'s registered agents matches `DtnPeer::get_best_cla()`'s
+/// choice. A successful attempt calls `report_successful_transmission()`
+/// on the peer; a failed one calls `report_failed_transmission()`.
+/// Intended to be run periodically alongside `process_peers()`, passing
+/// the running `DtnCore::cl_list`.
+pub fn process_peer_conn_states(cl_list: &[Box<dyn ConvergencyLayerAgent>]) {
+    let now = Instant::now();
+    let due: Vec<String> = PEERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, peer)| {
+            matches!(peer.conn_state, PeerConnState::Waiting { next_try, .. } if next_try <= now)
+        })
+        .map(|(node, _)| node.clone())
+        .collect();
+
+    for node in due {
+        let sender = match PEERS.lock().unwrap().get(&node).and_then(DtnPeer::get_best_cla) {
+            Some(sender) => sender,
+            None => continue,
+        };
+        let attempt_start = Instant::now();
+        let success = cl_list
+            .iter()
+            .find(|agent| agent.name() == sender.agent)
+            .map(|agent| agent.probe(&sender))
+            .unwrap_or(false);
+        let rtt = attempt_start.elapsed();
+
+        if let Some(peer) = PEERS.lock().unwrap().get_mut(&node) {
+            debug!("peer {} reconnection attempt over {}: {}", node, sender.agent, success);
+            if success {
+                peer.report_successful_transmission();
+                // The probe above is itself a real transmission/ack round
+                // trip over `sender.agent`, so its latency is a genuine
+                // RTT sample, not a synthetic one.
+                peer.add_rtt_sample(&sender.agent, rtt);
+            } else {
+                peer.report_failed_transmission();
+            }
+        }
+    }
 }
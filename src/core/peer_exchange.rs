@@ -0,0 +1,171 @@
+//! Anti-entropy peer exchange: periodically gossip a digest of the locally
+//! known peers so that nodes without direct contact can still learn about
+//! each other through a common neighbor.
+//!
+//! Each node computes a [`PeerListDigest`] over its sorted `PEERS` table.
+//! When two nodes compare digests and find them different, the side with
+//! the stale digest requests the full [`PeerList`] and merges in any
+//! entries it doesn't already know via [`merge_peer_list`]. Loop damping
+//! (`recently_learned`) is symmetric: a peer just learned through gossip
+//! is both skipped on re-merge and excluded from what gets advertised
+//! back out, via [`local_peer_list`] / [`compute_peer_list_digest`].
+
+use crate::core::{peer_store, DtnPeer, PeerAddress, PeerType};
+use crate::PEERS;
+use bp7::EndpointID;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often a node advertises its `PeerListDigest` to its neighbors.
+pub const GOSSIP_INTERVAL: Duration = Duration::from_secs(300);
+/// Upper bound on how many peers a single exchange may add, to keep a
+/// misbehaving or stale neighbor from flooding the local peer table.
+pub const MAX_MERGE_PEERS: usize = 100;
+
+/// A compact summary of a node's peer table, compared instead of shipping
+/// the full `PeerList` on every gossip round.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PeerListDigest {
+    pub digest: [u8; 32],
+}
+
+/// The entries exchanged when two digests disagree: enough to reconstruct
+/// an unknown peer's `DtnPeer` entry.
+pub type PeerList = Vec<(EndpointID, PeerAddress, Vec<(String, Option<u16>)>)>;
+
+/// Computes the current digest over the locally known peers, sorted by
+/// `(node_name, cla_list)` so the hash is independent of `PEERS`'s
+/// (unordered) iteration order.
+///
+/// Excludes peers that `recently_learned` records as learned less than
+/// `GOSSIP_INTERVAL` ago; see `merge_peer_list()` for why.
+///
+/// Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use dtn7::core::peer_exchange::*;
+///
+/// let empty = HashMap::new();
+/// let before = compute_peer_list_digest(&empty);
+///
+/// let remote = vec![(
+///     bp7::EndpointID::new("dtn://node2/").unwrap(),
+///     dtn7::core::PeerAddress::Generic("node2-addr".into()),
+///     vec![("mtcp".to_string(), None)],
+/// )];
+/// let mut recently_learned = HashMap::new();
+/// merge_peer_list(remote, &mut recently_learned);
+///
+/// // The freshly learned peer is damped, so the digest doesn't move yet.
+/// assert_eq!(compute_peer_list_digest(&recently_learned), before);
+/// // But it's already reflected once damping no longer excludes it.
+/// assert_ne!(compute_peer_list_digest(&empty), before);
+/// ```
+pub fn compute_peer_list_digest(recently_learned: &HashMap<String, Instant>) -> PeerListDigest {
+    let mut entries: Vec<(String, Vec<(String, Option<u16>)>)> = PEERS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|p| !learned_within_gossip_interval(recently_learned, &p.get_node_name()))
+        .map(|p| (p.get_node_name(), p.cla_list.clone()))
+        .collect();
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    for (node_name, cla_list) in entries {
+        hasher.update(node_name.as_bytes());
+        for (agent, port) in cla_list {
+            hasher.update(agent.as_bytes());
+            hasher.update(port.unwrap_or_default().to_be_bytes());
+        }
+    }
+    PeerListDigest {
+        digest: hasher.finalize().into(),
+    }
+}
+
+/// Snapshot of the local peer table in the wire format exchanged once two
+/// nodes have found their digests disagree.
+///
+/// Excludes peers that `recently_learned` records as learned less than
+/// `GOSSIP_INTERVAL` ago; see `merge_peer_list()` for why.
+pub fn local_peer_list(recently_learned: &HashMap<String, Instant>) -> PeerList {
+    PEERS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|p| !learned_within_gossip_interval(recently_learned, &p.get_node_name()))
+        .map(|p| (p.eid.clone(), p.addr.clone(), p.cla_list.clone()))
+        .collect()
+}
+
+/// Whether `node_name` was learned through gossip less than
+/// `GOSSIP_INTERVAL` ago, according to `recently_learned`.
+fn learned_within_gossip_interval(recently_learned: &HashMap<String, Instant>, node_name: &str) -> bool {
+    recently_learned
+        .get(node_name)
+        .map_or(false, |learned_at| learned_at.elapsed() < GOSSIP_INTERVAL)
+}
+
+/// Merges a remote `PeerList` into the local `PEERS` table.
+///
+/// Unknown entries are inserted via `peer_store::insert_peer()` as
+/// `PeerType::Dynamic` with a fresh `last_contact`. `recently_learned`
+/// implements loop damping, symmetrically on both the merge side here and
+/// the advertising side (`local_peer_list()`, `compute_peer_list_digest()`):
+/// a peer we ourselves learned through gossip within the last
+/// `GOSSIP_INTERVAL` is neither re-merged nor offered back out, so two
+/// nodes don't bounce the same discovery back and forth every round.
+/// Returns the number of peers actually inserted.
+///
+/// Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use bp7::EndpointID;
+/// use dtn7::core::peer_exchange::*;
+/// use dtn7::core::PeerAddress;
+///
+/// let remote = vec![(
+///     EndpointID::new("dtn://node1/").unwrap(),
+///     PeerAddress::Generic("node1-addr".into()),
+///     vec![("mtcp".to_string(), None)],
+/// )];
+/// let mut recently_learned = HashMap::new();
+/// assert_eq!(merge_peer_list(remote.clone(), &mut recently_learned), 1);
+///
+/// // Damped: re-merging the same peer within GOSSIP_INTERVAL is a no-op,
+/// // and it's excluded from what we'd advertise back out, too, so the
+/// // node we just learned it from doesn't get it bounced straight back.
+/// assert_eq!(merge_peer_list(remote, &mut recently_learned), 0);
+/// assert!(!local_peer_list(&recently_learned)
+///     .iter()
+///     .any(|(eid, _, _)| eid.node_part().unwrap_or_default() == "node1"));
+/// ```
+pub fn merge_peer_list(
+    remote: PeerList,
+    recently_learned: &mut HashMap<String, Instant>,
+) -> usize {
+    let now = Instant::now();
+    recently_learned.retain(|_, learned_at| now.duration_since(*learned_at) < GOSSIP_INTERVAL);
+
+    let mut inserted = 0;
+    for (eid, addr, cla_list) in remote {
+        if inserted >= MAX_MERGE_PEERS {
+            break;
+        }
+        let node_name = eid.node_part().unwrap_or_default();
+        if recently_learned.contains_key(&node_name) {
+            continue;
+        }
+        let peer = DtnPeer::new(eid, addr, PeerType::Dynamic, cla_list);
+        if peer_store::insert_peer(peer) {
+            recently_learned.insert(node_name, now);
+            inserted += 1;
+        }
+    }
+    inserted
+}
@@ -0,0 +1,234 @@
+//! Pluggable persistence for the peer table; see `PEER_STORE`,
+//! `insert_peer()` and `remove_peer()` for the write-through path that
+//! keeps `PEERS` and the active store from drifting apart.
+
+use crate::core::{DtnPeer, PeerType};
+use crate::PEERS;
+use lazy_static::lazy_static;
+use log::error;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// A backend capable of durably persisting the peer table.
+///
+/// Implementors must not call back into the global `PEERS` mutex from
+/// within these methods; `insert_peer()` calls `put()` while holding it.
+pub trait PeerStore: Debug + Send {
+    /// Loads all previously persisted peers, e.g. on daemon startup.
+    fn load_all(&self) -> Vec<DtnPeer>;
+    /// Persists a newly discovered or updated peer.
+    fn put(&mut self, peer: &DtnPeer);
+    /// Removes a peer that has aged out or been abandoned.
+    fn remove(&mut self, node_name: &str);
+}
+
+/// Default backend: keeps nothing across restarts, matching today's
+/// behavior. Useful for tests and for deployments that don't care about
+/// rediscovering dynamic peers after a restart.
+#[derive(Debug, Default)]
+pub struct InMemoryPeerStore {}
+
+impl InMemoryPeerStore {
+    pub fn new() -> InMemoryPeerStore {
+        InMemoryPeerStore {}
+    }
+}
+
+impl PeerStore for InMemoryPeerStore {
+    fn load_all(&self) -> Vec<DtnPeer> {
+        Vec::new()
+    }
+    fn put(&mut self, _peer: &DtnPeer) {}
+    fn remove(&mut self, _node_name: &str) {}
+}
+
+/// SQLite-backed store. Each row mirrors a `DtnPeer`: `eid`, `addr`
+/// (stored as `PeerAddress`'s tagged `Display` form and parsed back
+/// losslessly for every variant), `con_type`, `cla_list` (JSON-encoded)
+/// and `last_contact`.
+#[derive(Debug)]
+pub struct SqlitePeerStore {
+    conn: rusqlite::Connection,
+}
+
+impl SqlitePeerStore {
+    /// Opens (creating if needed) the SQLite peer database at `db_path`.
+    /// Returns an error instead of panicking so a transient failure to
+    /// open the configured path doesn't have to be fatal for the caller.
+    pub fn new(db_path: &str) -> rusqlite::Result<SqlitePeerStore> {
+        let conn = rusqlite::Connection::open(db_path)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS peers (
+                node_name   TEXT PRIMARY KEY,
+                eid         TEXT NOT NULL,
+                addr        TEXT NOT NULL,
+                con_type    TEXT NOT NULL,
+                cla_list    TEXT NOT NULL,
+                last_contact INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(SqlitePeerStore { conn })
+    }
+}
+
+impl PeerStore for SqlitePeerStore {
+    fn load_all(&self) -> Vec<DtnPeer> {
+        let mut stmt = match self
+            .conn
+            .prepare("SELECT eid, addr, con_type, cla_list, last_contact FROM peers")
+        {
+            Ok(stmt) => stmt,
+            Err(err) => {
+                error!("failed to prepare peer load query: {}", err);
+                return Vec::new();
+            }
+        };
+        let rows = match stmt.query_map([], |row| {
+            let eid: String = row.get(0)?;
+            let addr: String = row.get(1)?;
+            let con_type: String = row.get(2)?;
+            let cla_list: String = row.get(3)?;
+            let last_contact: u64 = row.get(4)?;
+            Ok((eid, addr, con_type, cla_list, last_contact))
+        }) {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("failed to read persisted peers: {}", err);
+                return Vec::new();
+            }
+        };
+
+        let mut peers = Vec::new();
+        for row in rows.flatten() {
+            let (eid, addr, con_type, cla_list, last_contact) = row;
+            let eid: bp7::EndpointID = match eid.parse() {
+                Ok(eid) => eid,
+                Err(_) => continue,
+            };
+            let addr = crate::core::PeerAddress::from_str(&addr).expect("infallible");
+            let con_type = if con_type == "Static" {
+                PeerType::Static
+            } else {
+                PeerType::Dynamic
+            };
+            let cla_list: Vec<(String, Option<u16>)> =
+                serde_json::from_str(&cla_list).unwrap_or_default();
+
+            let mut peer = DtnPeer::new(eid, addr, con_type.clone(), cla_list);
+            peer.last_contact = last_contact;
+
+            // Dynamic peers whose recorded last_contact is already stale
+            // are dropped rather than reloaded; they'll be rediscovered
+            // via IPND or peer exchange if still around.
+            if con_type == PeerType::Dynamic && !peer.still_valid() {
+                continue;
+            }
+            peers.push(peer);
+        }
+        peers
+    }
+
+    fn put(&mut self, peer: &DtnPeer) {
+        let cla_list = serde_json::to_string(&peer.cla_list).unwrap_or_default();
+        let con_type = match peer.con_type {
+            PeerType::Static => "Static",
+            PeerType::Dynamic => "Dynamic",
+        };
+        let result = self.conn.execute(
+            "INSERT INTO peers (node_name, eid, addr, con_type, cla_list, last_contact)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(node_name) DO UPDATE SET
+                eid = excluded.eid,
+                addr = excluded.addr,
+                con_type = excluded.con_type,
+                cla_list = excluded.cla_list,
+                last_contact = excluded.last_contact",
+            rusqlite::params![
+                peer.get_node_name(),
+                peer.eid.to_string(),
+                peer.addr.to_string(),
+                con_type,
+                cla_list,
+                peer.last_contact,
+            ],
+        );
+        // A transient I/O error (lock contention, disk full, ...) here
+        // must not take the daemon down with it; PEERS already has the
+        // authoritative in-memory state, so we just log and the peer
+        // stays un-persisted until the next successful write.
+        if let Err(err) = result {
+            error!("failed to persist peer {}: {}", peer.get_node_name(), err);
+        }
+    }
+
+    fn remove(&mut self, node_name: &str) {
+        if let Err(err) = self
+            .conn
+            .execute("DELETE FROM peers WHERE node_name = ?1", [node_name])
+        {
+            error!("failed to remove persisted peer {}: {}", node_name, err);
+        }
+    }
+}
+
+/// Reloads peers from `store` into an in-memory map, ready to seed the
+/// global `PEERS` table on startup.
+pub fn reload_peers(store: &dyn PeerStore) -> HashMap<String, DtnPeer> {
+    store
+        .load_all()
+        .into_iter()
+        .map(|p| (p.get_node_name(), p))
+        .collect()
+}
+
+lazy_static! {
+    /// The active write-through backend for the peer table. Defaults to
+    /// `InMemoryPeerStore`, i.e. today's non-persistent behavior; call
+    /// `set_peer_store()` during daemon startup to switch to
+    /// `SqlitePeerStore`.
+    pub static ref PEER_STORE: Mutex<Box<dyn PeerStore>> =
+        Mutex::new(Box::new(InMemoryPeerStore::new()));
+}
+
+/// Swaps in a different `PeerStore` backend, e.g. a `SqlitePeerStore`
+/// configured from `CONFIG`. Does not itself reload `PEERS`; call
+/// `seed_peers_from_store()` afterwards.
+pub fn set_peer_store(store: Box<dyn PeerStore>) {
+    *PEER_STORE.lock().unwrap() = store;
+}
+
+/// Loads peers from the active `PEER_STORE` into the global `PEERS`
+/// table. Intended to be called once during daemon startup, before
+/// `process_peers()` runs for the first time.
+pub fn seed_peers_from_store() {
+    let loaded = reload_peers(&**PEER_STORE.lock().unwrap());
+    PEERS.lock().unwrap().extend(loaded);
+}
+
+/// Inserts a newly discovered peer into `PEERS` and persists it through
+/// `PEER_STORE`, unless a peer for that node is already known. Returns
+/// whether the peer was inserted.
+///
+/// `PeerStore` implementors are documented not to touch `PEERS`
+/// themselves, so locking `PEERS` first and `PEER_STORE` second here
+/// can't recurse back into either mutex.
+pub fn insert_peer(peer: DtnPeer) -> bool {
+    let mut peers = PEERS.lock().unwrap();
+    let node_name = peer.get_node_name();
+    if peers.contains_key(&node_name) {
+        return false;
+    }
+    PEER_STORE.lock().unwrap().put(&peer);
+    peers.insert(node_name, peer);
+    true
+}
+
+/// Removes a peer from both `PEERS` and `PEER_STORE`, e.g. once
+/// `process_peers()` has decided it aged out or was abandoned.
+pub fn remove_peer(node_name: &str) {
+    PEERS.lock().unwrap().remove(node_name);
+    PEER_STORE.lock().unwrap().remove(node_name);
+}
@@ -0,0 +1,71 @@
+//! Entry points for dispatching a received administrative record or
+//! other typed control payload once a bundle has been accepted from a
+//! CLA.
+
+use crate::core::protocol_handler::CUSTOM_PROTOCOL_RANGE;
+use crate::core::DtnCore;
+use bp7::{Bundle, EndpointID};
+
+/// Bundle control flag marking the payload as an administrative record
+/// (BPv7 primary block flag bit 0x02).
+const ADMINISTRATIVE_RECORD_FLAG: u64 = 0x02;
+
+/// Routes `payload` by `code`: codes in `CUSTOM_PROTOCOL_RANGE` go to
+/// whichever handler was registered via
+/// `DtnCore::register_protocol_handler()`; for any other code this
+/// returns `None` and does nothing else. Standard administrative records
+/// (status reports, custody signals) are not handled here.
+///
+/// Example
+///
+/// ```
+/// use bp7::EndpointID;
+/// use dtn7::core::processing::dispatch_administrative_record;
+/// use dtn7::core::protocol_handler::ProtocolHandler;
+/// use dtn7::core::DtnCore;
+///
+/// #[derive(Debug)]
+/// struct NoopHandler;
+/// impl ProtocolHandler for NoopHandler {
+///     fn code_range(&self) -> (u8, u8) {
+///         (200, 200)
+///     }
+///     fn handle(&mut self, _code: u8, _payload: &[u8], _sender: &EndpointID) -> Option<Vec<u8>> {
+///         None
+///     }
+/// }
+///
+/// let mut core = DtnCore::new();
+/// core.register_protocol_handler(NoopHandler);
+/// let sender = EndpointID::new("dtn://node1/").unwrap();
+///
+/// // In range: routed to the registered handler.
+/// assert!(dispatch_administrative_record(&mut core, 200, b"", &sender).is_none());
+/// // Out of range: not our concern, falls through.
+/// assert!(dispatch_administrative_record(&mut core, 1, b"", &sender).is_none());
+/// ```
+pub fn dispatch_administrative_record(
+    core: &mut DtnCore,
+    code: u8,
+    payload: &[u8],
+    sender: &EndpointID,
+) -> Option<Vec<u8>> {
+    if code >= CUSTOM_PROTOCOL_RANGE.0 && code <= CUSTOM_PROTOCOL_RANGE.1 {
+        return core.dispatch_protocol_message(code, payload, sender);
+    }
+    None
+}
+
+/// Real entry point for an accepted bundle: if `bundle` is an
+/// administrative record, its payload's leading byte is the record's type
+/// code, and the rest is routed through `dispatch_administrative_record`.
+/// Call this from wherever a CLA hands a newly received bundle up to core
+/// (the IPND/CLA receive loop, outside this file's scope).
+pub fn handle_received_bundle(core: &mut DtnCore, bundle: &Bundle) -> Option<Vec<u8>> {
+    if bundle.primary.bundle_control_flags & ADMINISTRATIVE_RECORD_FLAG == 0 {
+        return None;
+    }
+    let payload = bundle.payload()?;
+    let (code, rest) = payload.split_first()?;
+    dispatch_administrative_record(core, *code, rest, &bundle.primary.source)
+}
@@ -0,0 +1,66 @@
+//! Routes administrative-record / message-type codes in
+//! [`CUSTOM_PROTOCOL_RANGE`] to a registered `ProtocolHandler` instead of
+//! `core::processing` handling them inline. See `core::processing::dispatch_administrative_record`
+//! for the call site.
+
+use bp7::EndpointID;
+use std::fmt::Debug;
+
+/// Inclusive range of administrative-record / message-type codes set
+/// aside for downstream-registered `ProtocolHandler`s, mirroring the
+/// private/experimental-use convention of other BP type-code ranges.
+pub const CUSTOM_PROTOCOL_RANGE: (u8, u8) = (192, 255);
+
+/// Handles a self-contained, typed control message addressed to a code in
+/// its `code_range()`.
+pub trait ProtocolHandler: Debug {
+    /// Inclusive `(low, high)` range of codes this handler claims. Must
+    /// fall within `CUSTOM_PROTOCOL_RANGE`.
+    fn code_range(&self) -> (u8, u8);
+
+    /// Handles `payload` received from `sender` under `code`. May return a
+    /// response payload, which `dispatch()` passes back to its caller
+    /// unchanged; no response bundle is built from it in this crate.
+    fn handle(&mut self, code: u8, payload: &[u8], sender: &EndpointID) -> Option<Vec<u8>>;
+}
+
+/// Registry of `ProtocolHandler`s, owned by `DtnCore` alongside its
+/// `ApplicationAgent` endpoints.
+#[derive(Debug, Default)]
+pub struct ProtocolHandlerRegistry {
+    handlers: Vec<Box<dyn ProtocolHandler + Send>>,
+}
+
+impl ProtocolHandlerRegistry {
+    pub fn new() -> ProtocolHandlerRegistry {
+        ProtocolHandlerRegistry {
+            handlers: Vec::new(),
+        }
+    }
+
+    pub fn register<T: 'static + ProtocolHandler + Send>(&mut self, handler: T) {
+        let (lo, hi) = handler.code_range();
+        debug_assert!(
+            lo >= CUSTOM_PROTOCOL_RANGE.0 && hi <= CUSTOM_PROTOCOL_RANGE.1,
+            "protocol handler code range must fall within CUSTOM_PROTOCOL_RANGE"
+        );
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Routes `payload` to the first registered handler whose
+    /// `code_range()` contains `code`, if any.
+    pub fn dispatch(
+        &mut self,
+        code: u8,
+        payload: &[u8],
+        sender: &EndpointID,
+    ) -> Option<Vec<u8>> {
+        for handler in self.handlers.iter_mut() {
+            let (lo, hi) = handler.code_range();
+            if code >= lo && code <= hi {
+                return handler.handle(code, payload, sender);
+            }
+        }
+        None
+    }
+}